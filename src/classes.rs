@@ -0,0 +1,87 @@
+//! Named character-class configuration loaded from a file.
+
+use std::{collections::HashMap, path::Path};
+
+use serde::Deserialize;
+use snafu::{OptionExt, ResultExt, Snafu};
+
+use crate::SymbolsSet;
+
+/// A collection of user-defined named character classes, as loaded via
+/// `--classes`.
+#[derive(Debug, Deserialize)]
+pub struct ClassesConfig {
+    #[serde(flatten)]
+    classes: HashMap<String, String>,
+}
+
+/// Error loading or using a [`ClassesConfig`].
+#[derive(Debug, Snafu)]
+pub enum Error {
+    /// Unable to read the classes file.
+    #[snafu(display("Unable to read classes file {path:?}"))]
+    Read {
+        /// Path that failed to read.
+        path: std::path::PathBuf,
+        /// Source error.
+        source: std::io::Error,
+    },
+
+    /// Unable to parse the classes file as TOML.
+    #[snafu(display("Unable to parse {path:?} as TOML"))]
+    ParseToml {
+        /// Path that failed to parse.
+        path: std::path::PathBuf,
+        /// Source error.
+        source: toml::de::Error,
+    },
+
+    /// Unable to parse the classes file as JSON.
+    #[snafu(display("Unable to parse {path:?} as JSON"))]
+    ParseJson {
+        /// Path that failed to parse.
+        path: std::path::PathBuf,
+        /// Source error.
+        source: serde_json::Error,
+    },
+
+    /// A `--use`/`--exclude` name was not found in the loaded config.
+    #[snafu(display("Unknown character class {name:?}"))]
+    UnknownClass {
+        /// Name that was looked up.
+        name: String,
+    },
+
+    /// A named class's symbols failed to parse into a [`SymbolsSet`].
+    #[snafu(display("Class {name:?} contains no symbols"))]
+    EmptyClass {
+        /// Name of the offending class.
+        name: String,
+    },
+}
+
+impl ClassesConfig {
+    /// Loads named classes from a file at `path`.
+    ///
+    /// Files ending in `.json` are parsed as JSON, anything else as TOML.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path).context(ReadSnafu { path })?;
+        if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+            serde_json::from_str(&content).context(ParseJsonSnafu { path })
+        } else {
+            toml::from_str(&content).context(ParseTomlSnafu { path })
+        }
+    }
+
+    /// Looks up a named class and parses it into a [`SymbolsSet`].
+    pub fn get(&self, name: &str) -> Result<SymbolsSet, Error> {
+        let symbols = self
+            .classes
+            .get(name)
+            .context(UnknownClassSnafu { name })?;
+        symbols
+            .parse()
+            .ok()
+            .context(EmptyClassSnafu { name })
+    }
+}
@@ -1,11 +1,17 @@
-use std::{collections::BTreeSet, num::NonZeroU32, str::FromStr};
+mod classes;
+
+use std::{collections::BTreeSet, num::NonZeroU32, path::PathBuf, str::FromStr};
 
 use arboard::Clipboard;
 #[cfg(target_os = "linux")]
 use arboard::SetExtLinux;
 
 use clap::Parser;
-use rand::{prelude::Distribution, seq::IteratorRandom, Rng};
+use rand::{
+    prelude::Distribution,
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
 use snafu::{OptionExt, ResultExt, Snafu};
 
 #[derive(Debug, Parser)]
@@ -45,6 +51,42 @@ struct Args {
     #[clap(short = 'd', long = "deny")]
     disallowed: Vec<SymbolsSet>,
 
+    /// Minimum amount of latin uppercase symbols to include.
+    #[clap(long, default_value_t = 0)]
+    min_upper: u32,
+
+    /// Minimum amount of latin lowercase symbols to include.
+    #[clap(long, default_value_t = 0)]
+    min_lower: u32,
+
+    /// Minimum amount of digits to include.
+    #[clap(long, default_value_t = 0)]
+    min_digits: u32,
+
+    /// Minimum amount of special symbols to include.
+    #[clap(long, default_value_t = 0)]
+    min_special: u32,
+
+    /// Load named character classes from a TOML or JSON file.
+    ///
+    /// The file maps class names to strings of symbols, e.g.
+    /// `hex = "0123456789abcdef"`. Use `--use`/`--exclude` to merge or
+    /// subtract the named classes.
+    #[clap(long)]
+    classes: Option<PathBuf>,
+
+    /// Merge in a named class loaded via `--classes`.
+    ///
+    /// You can repeat this multiple times to merge several named classes.
+    #[clap(long = "use")]
+    use_classes: Vec<String>,
+
+    /// Subtract a named class loaded via `--classes`.
+    ///
+    /// You can repeat this multiple times. Takes precedence over `--use`.
+    #[clap(long)]
+    exclude: Vec<String>,
+
     /// Be verbose.
     #[clap(short, long)]
     verbose: bool,
@@ -108,6 +150,33 @@ impl Distribution<char> for &'_ SymbolsSet {
     }
 }
 
+/// A [`SymbolsSet`] prepared for repeated sampling.
+///
+/// Building a [`SymbolsSet`] distribution directly makes every draw scan the
+/// underlying `BTreeSet` (`O(n)` per character), which gets slow for large
+/// sets or long passwords. A `PreparedSet` materializes the symbols into a
+/// `Vec<char>` once, so each draw is an `O(1)` index lookup instead.
+pub struct PreparedSet {
+    symbols: Vec<char>,
+}
+
+impl PreparedSet {
+    /// Materializes the symbols of `set` for `O(1)`-per-draw sampling.
+    pub fn new(set: &SymbolsSet) -> Self {
+        Self {
+            symbols: set.inner.iter().copied().collect(),
+        }
+    }
+}
+
+impl Distribution<char> for &'_ PreparedSet {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> char {
+        // `gen_range` draws uniformly from `0..len`, so every symbol is
+        // equally likely regardless of `len` -- no modulo bias.
+        self.symbols[rng.gen_range(0..self.symbols.len())]
+    }
+}
+
 impl<const N: usize> From<[char; N]> for SymbolsSet {
     fn from(list: [char; N]) -> Self {
         assert_ne!(N, 0);
@@ -167,6 +236,35 @@ fn check_args() {
     <Args as clap::CommandFactory>::command().debug_assert();
 }
 
+#[test]
+fn prepared_set_samples_are_unbiased() {
+    // Index sampling draws uniformly from `0..len`, so with enough draws
+    // every symbol should show up a roughly equal number of times.
+    let set = SymbolsSet::from(['a', 'b', 'c', 'd']);
+    let prepared = PreparedSet::new(&set);
+
+    let mut counts = std::collections::BTreeMap::<char, usize>::new();
+    for c in rand::rngs::OsRng.sample_iter(&prepared).take(40_000) {
+        *counts.entry(c).or_default() += 1;
+    }
+
+    assert_eq!(counts.keys().copied().collect::<Vec<_>>(), vec!['a', 'b', 'c', 'd']);
+    for count in counts.values() {
+        assert!(
+            (9_000..11_000).contains(count),
+            "sample counts should be roughly uniform, got {counts:?}"
+        );
+    }
+}
+
+#[test]
+fn prepared_set_is_never_empty() {
+    let set = SymbolsSet::from(['x']);
+    assert!(!set.is_empty());
+    let prepared = PreparedSet::new(&set);
+    assert_eq!(prepared.symbols.len(), 1);
+}
+
 /// Run error.
 #[derive(Debug, Snafu)]
 pub enum Error {
@@ -174,6 +272,36 @@ pub enum Error {
     #[snafu(display("No symbols are allowed to generate password with"))]
     EmptySet,
 
+    /// The sum of `--min-*` requirements exceeds the requested length.
+    #[snafu(display(
+        "Sum of minimum class requirements ({sum}) exceeds the requested length ({length})"
+    ))]
+    MinimumsExceedLength {
+        /// Sum of the requested minimums.
+        sum: u32,
+        /// Requested password length.
+        length: u32,
+    },
+
+    /// A `--min-*` flag was given for a class that has been turned off, or
+    /// whose symbols have all been removed via `--deny`/`--exclude`.
+    #[snafu(display("The '{class}' class is disabled, but a minimum was requested for it"))]
+    DisabledClassMinimum {
+        /// Name of the disabled class.
+        class: &'static str,
+    },
+
+    /// Unable to load the file given via `--classes`.
+    #[snafu(display("Unable to load classes config"))]
+    ClassesConfig {
+        /// Source error.
+        source: classes::Error,
+    },
+
+    /// `--use`/`--exclude` was given without `--classes`.
+    #[snafu(display("`--use`/`--exclude` requires `--classes <PATH>`"))]
+    MissingClassesConfig,
+
     /// Unable to initialize clipboard.
     #[snafu(display("Unable to initialize clipboard"))]
     InitClipboard {
@@ -214,8 +342,15 @@ fn run() -> Result<(), Error> {
         no_latin,
         no_digits,
         no_special,
-        allowed,
-        disallowed,
+        mut allowed,
+        mut disallowed,
+        min_upper,
+        min_lower,
+        min_digits,
+        min_special,
+        classes,
+        use_classes,
+        exclude,
         length,
         verbose,
         copy,
@@ -233,24 +368,73 @@ fn run() -> Result<(), Error> {
         .with_writer(std::io::stderr)
         .init();
 
+    let classes_config = match &classes {
+        Some(path) => Some(classes::ClassesConfig::load(path).context(ClassesConfigSnafu)?),
+        None => {
+            snafu::ensure!(
+                use_classes.is_empty() && exclude.is_empty(),
+                MissingClassesConfigSnafu
+            );
+            None
+        }
+    };
+    for name in &use_classes {
+        let config = classes_config.as_ref().expect("validated above");
+        allowed.push(config.get(name).context(ClassesConfigSnafu)?);
+    }
+    for name in &exclude {
+        let config = classes_config.as_ref().expect("validated above");
+        disallowed.push(config.get(name).context(ClassesConfigSnafu)?);
+    }
+
     let empty = (no_latin || (no_latin_upper && no_latin_lower))
         && no_digits
         && no_special
         && allowed.is_empty();
     snafu::ensure!(!empty, EmptySetSnafu);
 
+    let min_sum = min_upper + min_lower + min_digits + min_special;
+    snafu::ensure!(
+        min_sum <= length.get(),
+        MinimumsExceedLengthSnafu {
+            sum: min_sum,
+            length: length.get(),
+        }
+    );
+
     let mut maybe_set = None::<SymbolsSet>;
+    let mut upper_pool = None::<SymbolsSet>;
+    let mut lower_pool = None::<SymbolsSet>;
+    let mut digits_pool = None::<SymbolsSet>;
+    let mut special_pool = None::<SymbolsSet>;
+
     if !no_latin && !no_latin_upper {
-        merge(&mut maybe_set, SymbolsSet::from(LATIN_UPPER_SET));
+        let set = SymbolsSet::from(LATIN_UPPER_SET);
+        merge(&mut maybe_set, set.clone());
+        upper_pool = Some(set);
+    } else {
+        snafu::ensure!(min_upper == 0, DisabledClassMinimumSnafu { class: "upper" });
     }
     if !no_latin && !no_latin_lower {
-        merge(&mut maybe_set, SymbolsSet::from(LATIN_LOWER_SET))
+        let set = SymbolsSet::from(LATIN_LOWER_SET);
+        merge(&mut maybe_set, set.clone());
+        lower_pool = Some(set);
+    } else {
+        snafu::ensure!(min_lower == 0, DisabledClassMinimumSnafu { class: "lower" });
     }
     if !no_digits {
-        merge(&mut maybe_set, SymbolsSet::from(DIGITS_SET))
+        let set = SymbolsSet::from(DIGITS_SET);
+        merge(&mut maybe_set, set.clone());
+        digits_pool = Some(set);
+    } else {
+        snafu::ensure!(min_digits == 0, DisabledClassMinimumSnafu { class: "digits" });
     }
     if !no_special {
-        merge(&mut maybe_set, SymbolsSet::from(SPECIAL_SET))
+        let set = SymbolsSet::from(SPECIAL_SET);
+        merge(&mut maybe_set, set.clone());
+        special_pool = Some(set);
+    } else {
+        snafu::ensure!(min_special == 0, DisabledClassMinimumSnafu { class: "special" });
     }
     for allowed in allowed {
         merge(&mut maybe_set, allowed);
@@ -262,6 +446,18 @@ fn run() -> Result<(), Error> {
         result_symbols = result_symbols
             .subtract(&disallowed)
             .context(EmptySetSnafu)?;
+        if let Some(set) = upper_pool.take() {
+            upper_pool = set.subtract(&disallowed);
+        }
+        if let Some(set) = lower_pool.take() {
+            lower_pool = set.subtract(&disallowed);
+        }
+        if let Some(set) = digits_pool.take() {
+            digits_pool = set.subtract(&disallowed);
+        }
+        if let Some(set) = special_pool.take() {
+            special_pool = set.subtract(&disallowed);
+        }
     }
 
     let symbols = result_symbols;
@@ -271,10 +467,32 @@ fn run() -> Result<(), Error> {
         tracing::warn!("There is only one symbol available for password generation")
     }
 
-    let password: String = rand::rngs::OsRng
-        .sample_iter(&symbols)
-        .take(length.get() as usize)
-        .collect();
+    let entropy = f64::from(length.get()) * (symbols.len() as f64).log2();
+    tracing::debug!("Password entropy: {entropy:.2} bits");
+
+    let length = length.get() as usize;
+    let sampler = PreparedSet::new(&symbols);
+
+    let mut buffer = Vec::with_capacity(length);
+    for (class, pool, min) in [
+        ("upper", &upper_pool, min_upper),
+        ("lower", &lower_pool, min_lower),
+        ("digits", &digits_pool, min_digits),
+        ("special", &special_pool, min_special),
+    ] {
+        if min > 0 {
+            let pool = pool.as_ref().context(DisabledClassMinimumSnafu { class })?;
+            let prepared = PreparedSet::new(pool);
+            buffer.extend(rand::rngs::OsRng.sample_iter(&prepared).take(min as usize));
+        }
+    }
+    buffer.extend(
+        rand::rngs::OsRng
+            .sample_iter(&sampler)
+            .take(length - buffer.len()),
+    );
+    buffer.shuffle(&mut rand::rngs::OsRng);
+    let password: String = buffer.into_iter().collect();
     if copy {
         #[cfg(target_os = "linux")]
         {